@@ -0,0 +1,135 @@
+/*
+This module is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 30, 2017
+Last Modified: July 30, 2017
+License: MIT
+*/
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, Error, ErrorKind, Read, Write};
+use std::path::Path;
+use raster::RasterConfigs;
+use super::DecodedRaster;
+
+/// `file_name` is the sidecar text header; the binary data lives alongside with a `.bil` extension.
+fn data_path(file_name: &str) -> String {
+    Path::new(file_name).with_extension("bil").to_str().unwrap().to_string()
+}
+
+/// Writes a plain, row-major binary grid of little-endian 64-bit floats plus a text header.
+pub fn write(file_name: &str, configs: &RasterConfigs, data: &[f64], metadata: &[String]) -> Result<(), Error> {
+    let f = File::create(file_name)?;
+    let mut w = BufWriter::new(f);
+    writeln!(w, "NROWS {}", configs.rows)?;
+    writeln!(w, "NCOLS {}", configs.columns)?;
+    writeln!(w, "NORTH {}", configs.north)?;
+    writeln!(w, "SOUTH {}", configs.south)?;
+    writeln!(w, "EAST {}", configs.east)?;
+    writeln!(w, "WEST {}", configs.west)?;
+    writeln!(w, "NODATA {}", configs.nodata)?;
+    writeln!(w, "DATA_TYPE {}", configs.data_type.to_string())?;
+    writeln!(w, "PHOTOMETRIC_INTERP {}", configs.photometric_interp.to_string())?;
+    writeln!(w, "PALETTE {}", configs.palette)?;
+    writeln!(w, "BYTEORDER LITTLE_ENDIAN")?;
+    writeln!(w, "LAYOUT BIL")?;
+    for entry in metadata.iter() {
+        writeln!(w, "METADATA {}", entry)?;
+    }
+
+    let data_file = File::create(&data_path(file_name))?;
+    let mut data_writer = BufWriter::new(data_file);
+    for v in data.iter() {
+        data_writer.write_all(&v.to_bits().to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+pub fn read(file_name: &str) -> Result<DecodedRaster, Error> {
+    let f = File::open(file_name)?;
+    let reader = BufReader::new(f);
+
+    let mut configs = RasterConfigs::default();
+    let mut metadata = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let parts: Vec<&str> = line.trim().splitn(2, ' ').collect();
+        if parts.len() < 2 {
+            continue;
+        }
+        let (key, value) = (parts[0], parts[1].trim());
+        match key {
+            "NROWS" => configs.rows = value.parse().unwrap_or(0),
+            "NCOLS" => configs.columns = value.parse().unwrap_or(0),
+            "NORTH" => configs.north = value.parse().unwrap_or(0f64),
+            "SOUTH" => configs.south = value.parse().unwrap_or(0f64),
+            "EAST" => configs.east = value.parse().unwrap_or(0f64),
+            "WEST" => configs.west = value.parse().unwrap_or(0f64),
+            "NODATA" => configs.nodata = value.parse().unwrap_or(-32768f64),
+            "DATA_TYPE" => configs.data_type = ::raster::DataType::from_str(value),
+            "PHOTOMETRIC_INTERP" => configs.photometric_interp = ::raster::PhotometricInterpretation::from_str(value),
+            "PALETTE" => configs.palette = value.to_string(),
+            "METADATA" => metadata.push(value.to_string()),
+            _ => {}
+        }
+    }
+
+    configs.resolution_x = if configs.columns > 0 { (configs.east - configs.west) / configs.columns as f64 } else { 0f64 };
+    configs.resolution_y = if configs.rows > 0 { (configs.north - configs.south) / configs.rows as f64 } else { 0f64 };
+
+    let mut data_file = File::open(&data_path(file_name))?;
+    let n = configs.rows * configs.columns;
+    let mut buf = vec![0u8; n * 8];
+    data_file.read_exact(&mut buf)?;
+    let mut data = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&buf[i * 8..i * 8 + 8]);
+        data.push(f64::from_bits(u64::from_le_bytes(bytes)));
+    }
+
+    if data.len() != n {
+        return Err(Error::new(ErrorKind::InvalidData,
+                            "The band-interleaved data file is shorter than its header declares."));
+    }
+
+    Ok(DecodedRaster { configs: configs, data: data, metadata: metadata })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raster::{DataType, PhotometricInterpretation};
+
+    #[test]
+    fn round_trips_nodata_data_type_palette_and_metadata() {
+        let mut p = ::std::env::temp_dir();
+        p.push("whitebox_band_interleaved_round_trip_test.hdr");
+        let file_name = p.to_str().unwrap().to_string();
+
+        let mut configs = RasterConfigs::default();
+        configs.rows = 2;
+        configs.columns = 3;
+        configs.north = 50f64;
+        configs.south = 0f64;
+        configs.east = 30f64;
+        configs.west = 0f64;
+        configs.nodata = -1f64;
+        configs.data_type = DataType::U8;
+        configs.photometric_interp = PhotometricInterpretation::Boolean;
+        configs.palette = "qual.plt".to_string();
+        let data = vec![1f64, 0f64, -1f64, 1f64, 1f64, 0f64];
+        let metadata = vec!["converted by ConvertRaster".to_string()];
+
+        write(&file_name, &configs, &data, &metadata).unwrap();
+        let decoded = read(&file_name).unwrap();
+
+        assert_eq!(decoded.configs.nodata, configs.nodata);
+        assert_eq!(decoded.configs.data_type, DataType::U8);
+        assert_eq!(decoded.configs.photometric_interp, PhotometricInterpretation::Boolean);
+        assert_eq!(decoded.configs.palette, "qual.plt");
+        assert_eq!(decoded.data, data);
+        assert_eq!(decoded.metadata, metadata);
+    }
+}
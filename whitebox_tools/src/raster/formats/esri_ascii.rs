@@ -0,0 +1,129 @@
+/*
+This module is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 30, 2017
+Last Modified: July 30, 2017
+License: MIT
+*/
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, Error, ErrorKind, Write};
+use raster::RasterConfigs;
+use super::DecodedRaster;
+
+/// Writes an ESRI ASCII grid: a six-line key/value header followed by the data rows.
+pub fn write(file_name: &str, configs: &RasterConfigs, data: &[f64]) -> Result<(), Error> {
+    let f = File::create(file_name)?;
+    let mut w = BufWriter::new(f);
+
+    writeln!(w, "ncols        {}", configs.columns)?;
+    writeln!(w, "nrows        {}", configs.rows)?;
+    writeln!(w, "xllcorner    {}", configs.west)?;
+    writeln!(w, "yllcorner    {}", configs.south)?;
+    writeln!(w, "cellsize     {}", configs.resolution_x)?;
+    writeln!(w, "NODATA_value {}", configs.nodata)?;
+
+    let columns = configs.columns;
+    for row in 0..configs.rows {
+        let start = row * columns;
+        let line: Vec<String> = data[start..start + columns].iter().map(|v| v.to_string()).collect();
+        writeln!(w, "{}", line.join(" "))?;
+    }
+
+    Ok(())
+}
+
+/// Reads an ESRI ASCII grid written by `write`, above.
+pub fn read(file_name: &str) -> Result<DecodedRaster, Error> {
+    let f = File::open(file_name)?;
+    let reader = BufReader::new(f);
+
+    let mut configs = RasterConfigs::default();
+    let mut xllcorner = 0f64;
+    let mut yllcorner = 0f64;
+    let mut cellsize = 0f64;
+    let mut data: Vec<f64> = vec![];
+
+    for line in reader.lines() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let lower = trimmed.to_lowercase();
+        if lower.starts_with("ncols") {
+            configs.columns = parse_field(trimmed)?.round() as usize;
+        } else if lower.starts_with("nrows") {
+            configs.rows = parse_field(trimmed)?.round() as usize;
+        } else if lower.starts_with("xllcorner") {
+            xllcorner = parse_field(trimmed)?;
+        } else if lower.starts_with("yllcorner") {
+            yllcorner = parse_field(trimmed)?;
+        } else if lower.starts_with("cellsize") {
+            cellsize = parse_field(trimmed)?;
+        } else if lower.starts_with("nodata_value") {
+            configs.nodata = parse_field(trimmed)?;
+        } else {
+            for token in trimmed.split_whitespace() {
+                data.push(token.parse().map_err(|_| Error::new(ErrorKind::InvalidData,
+                                        format!("Could not parse grid value '{}' in ESRI ASCII grid.", token)))?);
+            }
+        }
+    }
+
+    if data.len() != configs.rows * configs.columns {
+        return Err(Error::new(ErrorKind::InvalidData,
+                            "The number of data values in the ESRI ASCII grid does not match its header."));
+    }
+
+    configs.resolution_x = cellsize;
+    configs.resolution_y = cellsize;
+    configs.west = xllcorner;
+    configs.south = yllcorner;
+    configs.east = xllcorner + cellsize * configs.columns as f64;
+    configs.north = yllcorner + cellsize * configs.rows as f64;
+    // The ESRI ASCII grid spec has no header slot for data type, photometric
+    // interpretation, or palette, so a converted grid always comes back as F32/Continuous.
+    configs.data_type = ::raster::DataType::F32;
+
+    Ok(DecodedRaster { configs: configs, data: data, metadata: vec![] })
+}
+
+fn parse_field(line: &str) -> Result<f64, Error> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 2 {
+        return Err(Error::new(ErrorKind::InvalidData, format!("Malformed ESRI ASCII grid header line: '{}'.", line)));
+    }
+    parts[1].parse().map_err(|_| Error::new(ErrorKind::InvalidData, format!("Malformed ESRI ASCII grid header line: '{}'.", line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_grid_extent_and_nodata() {
+        let mut p = ::std::env::temp_dir();
+        p.push("whitebox_esri_ascii_round_trip_test.asc");
+        let file_name = p.to_str().unwrap().to_string();
+
+        let mut configs = RasterConfigs::default();
+        configs.rows = 2;
+        configs.columns = 2;
+        configs.west = 0f64;
+        configs.south = 0f64;
+        configs.resolution_x = 5f64;
+        configs.nodata = -9999f64;
+        let data = vec![1.5f64, -9999f64, 3.25f64, 4f64];
+
+        write(&file_name, &configs, &data).unwrap();
+        let decoded = read(&file_name).unwrap();
+
+        assert_eq!(decoded.configs.rows, configs.rows);
+        assert_eq!(decoded.configs.columns, configs.columns);
+        assert_eq!(decoded.configs.nodata, configs.nodata);
+        assert_eq!(decoded.configs.west, configs.west);
+        assert_eq!(decoded.configs.south, configs.south);
+        assert_eq!(decoded.data, data);
+    }
+}
@@ -0,0 +1,274 @@
+/*
+This module is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 30, 2017
+Last Modified: July 30, 2017
+License: MIT
+*/
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use raster::RasterConfigs;
+use super::DecodedRaster;
+
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_DOUBLE: u16 = 12;
+
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC_INTERPRETATION: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_ROWS_PER_STRIP: u16 = 278;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_SAMPLE_FORMAT: u16 = 339;
+const TAG_MODEL_PIXEL_SCALE: u16 = 33550;
+const TAG_MODEL_TIEPOINT: u16 = 33922;
+
+const TYPE_ASCII: u16 = 2;
+
+// Private tag, in the range TIFF reserves for application-specific use. Carries the
+// whitebox_tools raster config fields that standard TIFF/GeoTIFF tags have no room for
+// (nodata, data type, photometric interpretation, palette), pipe-separated ASCII.
+const TAG_WB_RASTER_INFO: u16 = 65000;
+
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    value_or_offset: u32,
+}
+
+/// Writes a minimal single-band, single-strip, uncompressed, 64-bit-float GeoTIFF.
+pub fn write(file_name: &str, configs: &RasterConfigs, data: &[f64]) -> Result<(), Error> {
+    let rows = configs.rows as u32;
+    let columns = configs.columns as u32;
+
+    let wb_info = format!("{}|{}|{}|{}", configs.nodata, configs.data_type.to_string(),
+                           configs.photometric_interp.to_string(), configs.palette);
+    let wb_info_bytes = wb_info.into_bytes();
+
+    let header_size: u32 = 8;
+    let num_entries: u16 = 13;
+    let ifd_size: u32 = 2 + (num_entries as u32) * 12 + 4;
+    let ifd_offset: u32 = header_size;
+
+    let pixel_scale_offset = ifd_offset + ifd_size;
+    let pixel_scale_size: u32 = 3 * 8;
+    let tiepoint_offset = pixel_scale_offset + pixel_scale_size;
+    let tiepoint_size: u32 = 6 * 8;
+    let wb_info_offset = tiepoint_offset + tiepoint_size;
+    let data_offset = wb_info_offset + wb_info_bytes.len() as u32;
+    let data_size: u32 = rows * columns * 8;
+
+    let entries = vec![
+        IfdEntry { tag: TAG_IMAGE_WIDTH, field_type: TYPE_LONG, count: 1, value_or_offset: columns },
+        IfdEntry { tag: TAG_IMAGE_LENGTH, field_type: TYPE_LONG, count: 1, value_or_offset: rows },
+        IfdEntry { tag: TAG_BITS_PER_SAMPLE, field_type: TYPE_SHORT, count: 1, value_or_offset: 64 },
+        IfdEntry { tag: TAG_COMPRESSION, field_type: TYPE_SHORT, count: 1, value_or_offset: 1 },
+        IfdEntry { tag: TAG_PHOTOMETRIC_INTERPRETATION, field_type: TYPE_SHORT, count: 1, value_or_offset: 1 },
+        IfdEntry { tag: TAG_STRIP_OFFSETS, field_type: TYPE_LONG, count: 1, value_or_offset: data_offset },
+        IfdEntry { tag: TAG_SAMPLES_PER_PIXEL, field_type: TYPE_SHORT, count: 1, value_or_offset: 1 },
+        IfdEntry { tag: TAG_ROWS_PER_STRIP, field_type: TYPE_LONG, count: 1, value_or_offset: rows },
+        IfdEntry { tag: TAG_STRIP_BYTE_COUNTS, field_type: TYPE_LONG, count: 1, value_or_offset: data_size },
+        IfdEntry { tag: TAG_SAMPLE_FORMAT, field_type: TYPE_SHORT, count: 1, value_or_offset: 3 },
+        IfdEntry { tag: TAG_MODEL_PIXEL_SCALE, field_type: TYPE_DOUBLE, count: 3, value_or_offset: pixel_scale_offset },
+        IfdEntry { tag: TAG_MODEL_TIEPOINT, field_type: TYPE_DOUBLE, count: 6, value_or_offset: tiepoint_offset },
+        IfdEntry { tag: TAG_WB_RASTER_INFO, field_type: TYPE_ASCII, count: wb_info_bytes.len() as u32, value_or_offset: wb_info_offset },
+    ];
+
+    let f = File::create(file_name)?;
+    let mut w = BufWriter::new(f);
+
+    // Header: little-endian byte order marker, magic number 42, offset to first IFD.
+    w.write_all(b"II")?;
+    w.write_all(&42u16.to_le_bytes())?;
+    w.write_all(&ifd_offset.to_le_bytes())?;
+
+    // IFD.
+    w.write_all(&num_entries.to_le_bytes())?;
+    for entry in entries.iter() {
+        w.write_all(&entry.tag.to_le_bytes())?;
+        w.write_all(&entry.field_type.to_le_bytes())?;
+        w.write_all(&entry.count.to_le_bytes())?;
+        w.write_all(&entry.value_or_offset.to_le_bytes())?;
+    }
+    w.write_all(&0u32.to_le_bytes())?; // no further IFDs
+
+    // Out-of-line DOUBLE arrays: pixel scale (x, y, z) and the single tiepoint that pins
+    // raster (0, 0) to the model's (west, north, 0).
+    w.write_all(&configs.resolution_x.to_le_bytes())?;
+    w.write_all(&configs.resolution_y.to_le_bytes())?;
+    w.write_all(&0f64.to_le_bytes())?;
+
+    w.write_all(&0f64.to_le_bytes())?;
+    w.write_all(&0f64.to_le_bytes())?;
+    w.write_all(&0f64.to_le_bytes())?;
+    w.write_all(&configs.west.to_le_bytes())?;
+    w.write_all(&configs.north.to_le_bytes())?;
+    w.write_all(&0f64.to_le_bytes())?;
+
+    w.write_all(&wb_info_bytes)?;
+
+    // Pixel data, row-major, IEEE-754 double precision.
+    for v in data.iter() {
+        w.write_all(&v.to_bits().to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Reads back a GeoTIFF produced by `write`; not a general-purpose GeoTIFF reader.
+pub fn read(file_name: &str) -> Result<DecodedRaster, Error> {
+    let f = File::open(file_name)?;
+    let mut r = BufReader::new(f);
+
+    let mut byte_order = [0u8; 2];
+    r.read_exact(&mut byte_order)?;
+    if &byte_order != b"II" {
+        return Err(Error::new(ErrorKind::InvalidData, "Only little-endian GeoTIFF files are supported."));
+    }
+    r.read_exact(&mut [0u8; 2])?; // magic number (42), not validated
+    let mut ifd_offset_bytes = [0u8; 4];
+    r.read_exact(&mut ifd_offset_bytes)?;
+    let ifd_offset = u32::from_le_bytes(ifd_offset_bytes);
+
+    r.seek(SeekFrom::Start(ifd_offset as u64))?;
+    let mut count_bytes = [0u8; 2];
+    r.read_exact(&mut count_bytes)?;
+    let num_entries = u16::from_le_bytes(count_bytes);
+
+    let mut rows = 0u32;
+    let mut columns = 0u32;
+    let mut strip_offset = 0u32;
+    let mut pixel_scale_offset = 0u32;
+    let mut tiepoint_offset = 0u32;
+    let mut wb_info_offset = 0u32;
+    let mut wb_info_len = 0u32;
+
+    for _ in 0..num_entries {
+        let mut tag_bytes = [0u8; 2];
+        let mut type_bytes = [0u8; 2];
+        let mut count_bytes = [0u8; 4];
+        let mut value_bytes = [0u8; 4];
+        r.read_exact(&mut tag_bytes)?;
+        r.read_exact(&mut type_bytes)?;
+        r.read_exact(&mut count_bytes)?;
+        r.read_exact(&mut value_bytes)?;
+        let tag = u16::from_le_bytes(tag_bytes);
+        let count = u32::from_le_bytes(count_bytes);
+        let value = u32::from_le_bytes(value_bytes);
+        match tag {
+            TAG_IMAGE_WIDTH => columns = value,
+            TAG_IMAGE_LENGTH => rows = value,
+            TAG_STRIP_OFFSETS => strip_offset = value,
+            TAG_MODEL_PIXEL_SCALE => pixel_scale_offset = value,
+            TAG_MODEL_TIEPOINT => tiepoint_offset = value,
+            TAG_WB_RASTER_INFO => { wb_info_offset = value; wb_info_len = count; },
+            _ => {}
+        }
+    }
+
+    if rows == 0 || columns == 0 || strip_offset == 0 {
+        return Err(Error::new(ErrorKind::InvalidData, "GeoTIFF is missing required tags (ImageWidth/ImageLength/StripOffsets)."));
+    }
+
+    let mut configs = RasterConfigs::default();
+    configs.rows = rows as usize;
+    configs.columns = columns as usize;
+
+    let mut resolution_x = 0f64;
+    let mut resolution_y = 0f64;
+    if pixel_scale_offset > 0 {
+        r.seek(SeekFrom::Start(pixel_scale_offset as u64))?;
+        resolution_x = read_f64(&mut r)?;
+        resolution_y = read_f64(&mut r)?;
+    }
+
+    let mut west = 0f64;
+    let mut north = 0f64;
+    if tiepoint_offset > 0 {
+        r.seek(SeekFrom::Start(tiepoint_offset as u64))?;
+        let _raster_x = read_f64(&mut r)?;
+        let _raster_y = read_f64(&mut r)?;
+        let _raster_z = read_f64(&mut r)?;
+        west = read_f64(&mut r)?;
+        north = read_f64(&mut r)?;
+    }
+
+    configs.resolution_x = resolution_x;
+    configs.resolution_y = resolution_y;
+    configs.west = west;
+    configs.north = north;
+    configs.east = west + resolution_x * columns as f64;
+    configs.south = north - resolution_y * rows as f64;
+
+    if wb_info_offset > 0 && wb_info_len > 0 {
+        r.seek(SeekFrom::Start(wb_info_offset as u64))?;
+        let mut buf = vec![0u8; wb_info_len as usize];
+        r.read_exact(&mut buf)?;
+        let wb_info = String::from_utf8_lossy(&buf);
+        let parts: Vec<&str> = wb_info.trim_end_matches('\0').splitn(4, '|').collect();
+        if parts.len() == 4 {
+            configs.nodata = parts[0].parse().unwrap_or(configs.nodata);
+            configs.data_type = ::raster::DataType::from_str(parts[1]);
+            configs.photometric_interp = ::raster::PhotometricInterpretation::from_str(parts[2]);
+            configs.palette = parts[3].to_string();
+        }
+    } else {
+        configs.data_type = ::raster::DataType::F64;
+    }
+
+    r.seek(SeekFrom::Start(strip_offset as u64))?;
+    let n = (rows * columns) as usize;
+    let mut data = Vec::with_capacity(n);
+    for _ in 0..n {
+        data.push(read_f64(&mut r)?);
+    }
+
+    Ok(DecodedRaster { configs: configs, data: data, metadata: vec![] })
+}
+
+fn read_f64<R: Read>(r: &mut R) -> Result<f64, Error> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(f64::from_bits(u64::from_le_bytes(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raster::{DataType, PhotometricInterpretation};
+
+    #[test]
+    fn round_trips_nodata_data_type_and_palette() {
+        let mut p = ::std::env::temp_dir();
+        p.push("whitebox_geotiff_round_trip_test.tif");
+        let file_name = p.to_str().unwrap().to_string();
+
+        let mut configs = RasterConfigs::default();
+        configs.rows = 3;
+        configs.columns = 2;
+        configs.west = 100f64;
+        configs.north = 200f64;
+        configs.resolution_x = 10f64;
+        configs.resolution_y = 10f64;
+        configs.nodata = -9999f64;
+        configs.data_type = DataType::I16;
+        configs.photometric_interp = PhotometricInterpretation::Categorical;
+        configs.palette = "qual.plt".to_string();
+        let data = vec![1f64, 2f64, 3f64, -9999f64, 5f64, 6f64];
+
+        write(&file_name, &configs, &data).unwrap();
+        let decoded = read(&file_name).unwrap();
+
+        assert_eq!(decoded.configs.nodata, configs.nodata);
+        assert_eq!(decoded.configs.data_type, DataType::I16);
+        assert_eq!(decoded.configs.photometric_interp, PhotometricInterpretation::Categorical);
+        assert_eq!(decoded.configs.palette, "qual.plt");
+        assert_eq!(decoded.data, data);
+    }
+}
@@ -0,0 +1,101 @@
+/*
+This module is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 30, 2017
+Last Modified: July 30, 2017
+License: MIT
+*/
+mod geotiff;
+mod esri_ascii;
+mod band_interleaved;
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+use raster::RasterConfigs;
+
+/// On-disk raster encodings `Raster` can read and write, beyond its native `.dep`/`.tas` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RasterFormat {
+    /// The native whitebox `.dep` (header) / `.tas` (data) pair.
+    Native,
+    /// A minimal single-band, single-strip, uncompressed GeoTIFF.
+    GeoTiff,
+    /// An ESRI ASCII grid (`.asc`).
+    EsriAscii,
+    /// A plain row-major binary grid (`.bil`) with a text sidecar header (`.hdr`).
+    BandInterleaved,
+}
+
+/// A parsed raster, independent of which on-disk encoding it came from.
+pub struct DecodedRaster {
+    pub configs: RasterConfigs,
+    pub data: Vec<f64>,
+    pub metadata: Vec<String>,
+}
+
+/// Determines the on-disk encoding of `file_name` from its extension alone.
+pub fn detect_by_extension(file_name: &str) -> RasterFormat {
+    let ext = Path::new(file_name).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_ref() {
+        "tif" | "tiff" => RasterFormat::GeoTiff,
+        "asc" => RasterFormat::EsriAscii,
+        // ".hdr" is the canonical, addressable band-interleaved file name; the sibling
+        // ".bil" data file (see band_interleaved::data_path) is never passed to Raster
+        // directly, so it is deliberately not recognized here -- that would collide with
+        // the header's own extension-derivation logic.
+        "hdr" => RasterFormat::BandInterleaved,
+        _ => RasterFormat::Native,
+    }
+}
+
+/// Determines the on-disk encoding of an existing file from its content signature,
+/// falling back to `detect_by_extension` when nothing matches.
+pub fn detect(file_name: &str) -> RasterFormat {
+    let mut buf = [0u8; 16];
+    let bytes_read = match File::open(file_name) {
+        Ok(mut f) => f.read(&mut buf).unwrap_or(0),
+        Err(_) => 0,
+    };
+
+    if bytes_read >= 4 && (&buf[0..4] == b"II*\0" || &buf[0..4] == b"MM\0*") {
+        return RasterFormat::GeoTiff;
+    }
+
+    if bytes_read > 0 {
+        if let Ok(text) = ::std::str::from_utf8(&buf[0..bytes_read]) {
+            let trimmed = text.trim_start();
+            if trimmed.to_lowercase().starts_with("ncols") {
+                return RasterFormat::EsriAscii;
+            }
+            if trimmed.to_uppercase().starts_with("NROWS") {
+                return RasterFormat::BandInterleaved;
+            }
+            if trimmed.starts_with("Rows:") || trimmed.starts_with("Cols:") {
+                return RasterFormat::Native;
+            }
+        }
+    }
+
+    detect_by_extension(file_name)
+}
+
+pub fn read(format: RasterFormat, file_name: &str) -> Result<DecodedRaster, Error> {
+    match format {
+        RasterFormat::Native => Err(Error::new(ErrorKind::InvalidInput,
+                                "The native format is read directly by Raster::read, not through the format backend.")),
+        RasterFormat::GeoTiff => geotiff::read(file_name),
+        RasterFormat::EsriAscii => esri_ascii::read(file_name),
+        RasterFormat::BandInterleaved => band_interleaved::read(file_name),
+    }
+}
+
+pub fn write(format: RasterFormat, file_name: &str, configs: &RasterConfigs, data: &[f64], metadata: &[String]) -> Result<(), Error> {
+    match format {
+        RasterFormat::Native => Err(Error::new(ErrorKind::InvalidInput,
+                                "The native format is written directly by Raster::write, not through the format backend.")),
+        RasterFormat::GeoTiff => geotiff::write(file_name, configs, data),
+        RasterFormat::EsriAscii => esri_ascii::write(file_name, configs, data),
+        RasterFormat::BandInterleaved => band_interleaved::write(file_name, configs, data, metadata),
+    }
+}
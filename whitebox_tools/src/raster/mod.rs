@@ -0,0 +1,532 @@
+/*
+This module is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 2, 2017
+Last Modified: July 16, 2017
+License: MIT
+*/
+extern crate flate2;
+
+pub mod formats;
+
+use std::f64;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+use std::io::Error;
+use std::ops::Index;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use self::flate2::Compression;
+use self::flate2::read::ZlibDecoder;
+use self::flate2::write::ZlibEncoder;
+
+/// Number of grid rows packed into each compressed strip when `configs.compress` is set.
+pub const ROWS_PER_COMPRESSION_BLOCK: usize = 64;
+
+/// Written to the header file of a raster whose data file is block-compressed.
+const COMPRESSED_MARKER: &'static str = "COMPRESSED_BLOCKS_V1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    F64,
+    F32,
+    I32,
+    I16,
+    I8,
+    U32,
+    U16,
+    U8,
+    Unknown,
+}
+
+impl DataType {
+    fn to_string(&self) -> String {
+        match *self {
+            DataType::F64 => "F64".to_string(),
+            DataType::F32 => "F32".to_string(),
+            DataType::I32 => "I32".to_string(),
+            DataType::I16 => "I16".to_string(),
+            DataType::I8 => "I8".to_string(),
+            DataType::U32 => "U32".to_string(),
+            DataType::U16 => "U16".to_string(),
+            DataType::U8 => "U8".to_string(),
+            DataType::Unknown => "UNKNOWN".to_string(),
+        }
+    }
+
+    fn from_str(s: &str) -> DataType {
+        match s.to_uppercase().as_ref() {
+            "F64" => DataType::F64,
+            "F32" => DataType::F32,
+            "I32" => DataType::I32,
+            "I16" => DataType::I16,
+            "I8" => DataType::I8,
+            "U32" => DataType::U32,
+            "U16" => DataType::U16,
+            "U8" => DataType::U8,
+            _ => DataType::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotometricInterpretation {
+    Continuous,
+    Categorical,
+    Boolean,
+    Rgb,
+    Unknown,
+}
+
+impl PhotometricInterpretation {
+    fn to_string(&self) -> String {
+        match *self {
+            PhotometricInterpretation::Continuous => "CONTINUOUS".to_string(),
+            PhotometricInterpretation::Categorical => "CATEGORICAL".to_string(),
+            PhotometricInterpretation::Boolean => "BOOLEAN".to_string(),
+            PhotometricInterpretation::Rgb => "RGB".to_string(),
+            PhotometricInterpretation::Unknown => "UNKNOWN".to_string(),
+        }
+    }
+
+    fn from_str(s: &str) -> PhotometricInterpretation {
+        match s.to_uppercase().as_ref() {
+            "CONTINUOUS" => PhotometricInterpretation::Continuous,
+            "CATEGORICAL" => PhotometricInterpretation::Categorical,
+            "BOOLEAN" => PhotometricInterpretation::Boolean,
+            "RGB" => PhotometricInterpretation::Rgb,
+            _ => PhotometricInterpretation::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RasterConfigs {
+    pub rows: usize,
+    pub columns: usize,
+    pub north: f64,
+    pub south: f64,
+    pub east: f64,
+    pub west: f64,
+    pub resolution_x: f64,
+    pub resolution_y: f64,
+    pub nodata: f64,
+    pub data_type: DataType,
+    pub photometric_interp: PhotometricInterpretation,
+    pub palette: String,
+    pub z_units: String,
+    pub xy_units: String,
+    pub projection: String,
+    pub display_min: f64,
+    pub display_max: f64,
+    /// When true, `write()` compresses the data grid in fixed row-strip blocks.
+    pub compress: bool,
+}
+
+impl Default for RasterConfigs {
+    fn default() -> RasterConfigs {
+        RasterConfigs {
+            rows: 0,
+            columns: 0,
+            north: 0f64,
+            south: 0f64,
+            east: 0f64,
+            west: 0f64,
+            resolution_x: 0f64,
+            resolution_y: 0f64,
+            nodata: -32768f64,
+            data_type: DataType::F32,
+            photometric_interp: PhotometricInterpretation::Continuous,
+            palette: "grey.plt".to_string(),
+            z_units: "not specified".to_string(),
+            xy_units: "not specified".to_string(),
+            projection: "not specified".to_string(),
+            display_min: f64::NAN,
+            display_max: f64::NAN,
+            compress: false,
+        }
+    }
+}
+
+/// A grid backed by a raster file on disk: the native `.dep` header / `.tas` data file pair,
+/// or one of the encodings in `formats` (GeoTIFF, ESRI ASCII grid, band-interleaved binary).
+pub struct Raster {
+    pub file_name: String,
+    pub file_mode: String,
+    pub configs: RasterConfigs,
+    data: Vec<f64>,
+    metadata: Vec<String>,
+    block_index: Vec<(u64, u64)>,
+    data_file: Option<Mutex<File>>,
+    /// One cell per compression block, sized once `block_index` is known; `get_or_init`
+    /// decompresses a block at most once and hands back a safe `&Box<[f64]>` from `&self`.
+    block_cache: Vec<OnceLock<Box<[f64]>>>,
+}
+
+impl Raster {
+    /// Opens a raster for reading (`file_mode == "r"`), or creates an empty one otherwise.
+    pub fn new(file_name: &str, file_mode: &str) -> Result<Raster, Error> {
+        let mut r = Raster {
+            file_name: file_name.to_string(),
+            file_mode: file_mode.to_string(),
+            configs: RasterConfigs::default(),
+            data: vec![],
+            metadata: vec![],
+            block_index: vec![],
+            data_file: None,
+            block_cache: vec![],
+        };
+        if file_mode == "r" {
+            r.read()?;
+        }
+        Ok(r)
+    }
+
+    /// Creates a new output raster, copying dimensions/extent from `base_raster` and
+    /// pre-filling the data with nodata. Compression is never inherited.
+    pub fn initialize_using_file(file_name: &str, base_raster: &Raster) -> Raster {
+        let mut configs = base_raster.configs.clone();
+        configs.compress = false;
+        let data = vec![configs.nodata; configs.rows * configs.columns];
+        Raster {
+            file_name: file_name.to_string(),
+            file_mode: "w".to_string(),
+            configs: configs,
+            data: data,
+            metadata: vec![],
+            block_index: vec![],
+            data_file: None,
+            block_cache: vec![],
+        }
+    }
+
+    pub fn add_metadata_entry(&mut self, entry: String) {
+        self.metadata.push(entry);
+    }
+
+    pub fn set_row_data(&mut self, row: isize, data: Vec<f64>) {
+        let columns = self.configs.columns;
+        let start = row as usize * columns;
+        for col in 0..columns {
+            self.data[start + col] = data[col];
+        }
+    }
+
+    pub fn set_value(&mut self, row: isize, col: isize, value: f64) {
+        if row < 0 || col < 0 || row as usize >= self.configs.rows || col as usize >= self.configs.columns {
+            return;
+        }
+        let idx = row as usize * self.configs.columns + col as usize;
+        self.data[idx] = value;
+    }
+
+    /// Materializes `self.data` from `block_cache` for a raster that was read compressed.
+    pub fn load_all(&mut self) {
+        if !self.configs.compress || self.data.len() == self.configs.rows * self.configs.columns {
+            return;
+        }
+        let columns = self.configs.columns;
+        let mut data = vec![0f64; self.configs.rows * columns];
+        for row in 0..self.configs.rows {
+            let block = row / ROWS_PER_COMPRESSION_BLOCK;
+            let row_in_block = row % ROWS_PER_COMPRESSION_BLOCK;
+            let block_data = self.block_cache[block].get_or_init(|| self.decompress_block(block).into_boxed_slice());
+            data[row * columns..(row + 1) * columns]
+                .copy_from_slice(&block_data[row_in_block * columns..(row_in_block + 1) * columns]);
+        }
+        self.data = data;
+        self.configs.compress = false;
+    }
+
+    fn header_file(&self) -> String {
+        Raster::with_extension(&self.file_name, "dep")
+    }
+
+    fn data_file_path(&self) -> String {
+        Raster::with_extension(&self.file_name, "tas")
+    }
+
+    fn with_extension(file_name: &str, ext: &str) -> String {
+        let p = Path::new(file_name);
+        p.with_extension(ext).to_str().unwrap().to_string()
+    }
+
+    fn read(&mut self) -> Result<(), Error> {
+        let format = formats::detect(&self.file_name);
+        if format != formats::RasterFormat::Native {
+            let decoded = formats::read(format, &self.file_name)?;
+            self.configs = decoded.configs;
+            self.metadata = decoded.metadata;
+            self.data = decoded.data;
+            return Ok(());
+        }
+
+        let header_file = self.header_file();
+        let f = File::open(&header_file)?;
+        let reader = BufReader::new(f);
+        let mut compressed = false;
+        for line in reader.lines() {
+            let line = line?;
+            let parts: Vec<&str> = line.splitn(2, ':').collect();
+            if parts.len() < 2 {
+                continue;
+            }
+            let key = parts[0].trim();
+            let value = parts[1].trim();
+            match key {
+                "Rows" => self.configs.rows = value.parse().unwrap_or(0),
+                "Cols" => self.configs.columns = value.parse().unwrap_or(0),
+                "North" => self.configs.north = value.parse().unwrap_or(0f64),
+                "South" => self.configs.south = value.parse().unwrap_or(0f64),
+                "East" => self.configs.east = value.parse().unwrap_or(0f64),
+                "West" => self.configs.west = value.parse().unwrap_or(0f64),
+                "NoData" => self.configs.nodata = value.parse().unwrap_or(-32768f64),
+                "Data Type" => self.configs.data_type = DataType::from_str(value),
+                "Data Scale" => self.configs.photometric_interp = PhotometricInterpretation::from_str(value),
+                "Preferred Palette" => self.configs.palette = value.to_string(),
+                "Z Units" => self.configs.z_units = value.to_string(),
+                "XY Units" => self.configs.xy_units = value.to_string(),
+                "Projection" => self.configs.projection = value.to_string(),
+                "Compressed" => compressed = value == COMPRESSED_MARKER,
+                "Metadata Entry" => self.metadata.push(value.to_string()),
+                _ => {}
+            }
+        }
+        self.configs.compress = compressed;
+        self.configs.resolution_x = if self.configs.columns > 0 {
+            (self.configs.east - self.configs.west) / self.configs.columns as f64
+        } else {
+            0f64
+        };
+        self.configs.resolution_y = if self.configs.rows > 0 {
+            (self.configs.north - self.configs.south) / self.configs.rows as f64
+        } else {
+            0f64
+        };
+
+        let data_path = self.data_file_path();
+        if !compressed {
+            let mut f = File::open(&data_path)?;
+            let n = self.configs.rows * self.configs.columns;
+            let mut buf = vec![0u8; n * 8];
+            f.read_exact(&mut buf)?;
+            let mut data = Vec::with_capacity(n);
+            for i in 0..n {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&buf[i * 8..i * 8 + 8]);
+                data.push(f64::from_bits(u64::from_le_bytes(bytes)));
+            }
+            self.data = data;
+        } else {
+            let mut f = File::open(&data_path)?;
+            // The block index table lives at the end of the file: (offset: u64, compressed_len: u64)
+            // pairs, one per block, followed by a u64 block count as the file's last 8 bytes.
+            f.seek(SeekFrom::End(-8))?;
+            let mut count_bytes = [0u8; 8];
+            f.read_exact(&mut count_bytes)?;
+            let num_blocks = u64::from_le_bytes(count_bytes) as usize;
+            let table_size = (num_blocks as i64) * 16 + 8;
+            f.seek(SeekFrom::End(-table_size))?;
+            let mut block_index = Vec::with_capacity(num_blocks);
+            for _ in 0..num_blocks {
+                let mut offset_bytes = [0u8; 8];
+                let mut len_bytes = [0u8; 8];
+                f.read_exact(&mut offset_bytes)?;
+                f.read_exact(&mut len_bytes)?;
+                block_index.push((u64::from_le_bytes(offset_bytes), u64::from_le_bytes(len_bytes)));
+            }
+            self.block_cache = (0..block_index.len()).map(|_| OnceLock::new()).collect();
+            self.block_index = block_index;
+            self.data_file = Some(Mutex::new(f));
+            self.data = vec![];
+        }
+
+        Ok(())
+    }
+
+    /// Decompresses a single row-strip block, reading only its bytes from the data file.
+    fn decompress_block(&self, block: usize) -> Vec<f64> {
+        let (offset, len) = self.block_index[block];
+        let mut compressed_bytes = vec![0u8; len as usize];
+        {
+            let file = self.data_file.as_ref().expect("compressed raster missing data file handle");
+            let mut f = file.lock().unwrap();
+            f.seek(SeekFrom::Start(offset)).expect("failed to seek into compressed raster data file");
+            f.read_exact(&mut compressed_bytes).expect("failed to read compressed raster block");
+        }
+        let mut decoder = ZlibDecoder::new(&compressed_bytes[..]);
+        let mut raw = Vec::new();
+        decoder.read_to_end(&mut raw).expect("failed to inflate compressed raster block");
+        let n = raw.len() / 8;
+        let mut values = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&raw[i * 8..i * 8 + 8]);
+            values.push(f64::from_bits(u64::from_le_bytes(bytes)));
+        }
+        values
+    }
+
+    pub fn write(&mut self) -> Result<(), Error> {
+        match formats::detect_by_extension(&self.file_name) {
+            formats::RasterFormat::Native => if self.configs.compress {
+                self.write_compressed()
+            } else {
+                self.write_uncompressed()
+            },
+            other => formats::write(other, &self.file_name, &self.configs, &self.data, &self.metadata),
+        }
+    }
+
+    fn write_header(&self, compressed: bool) -> Result<(), Error> {
+        let f = File::create(&self.header_file())?;
+        let mut writer = BufWriter::new(f);
+        writeln!(writer, "Rows:\t{}", self.configs.rows)?;
+        writeln!(writer, "Cols:\t{}", self.configs.columns)?;
+        writeln!(writer, "North:\t{}", self.configs.north)?;
+        writeln!(writer, "South:\t{}", self.configs.south)?;
+        writeln!(writer, "East:\t{}", self.configs.east)?;
+        writeln!(writer, "West:\t{}", self.configs.west)?;
+        writeln!(writer, "NoData:\t{}", self.configs.nodata)?;
+        writeln!(writer, "Data Type:\t{}", self.configs.data_type.to_string())?;
+        writeln!(writer, "Data Scale:\t{}", self.configs.photometric_interp.to_string())?;
+        writeln!(writer, "Preferred Palette:\t{}", self.configs.palette)?;
+        writeln!(writer, "Z Units:\t{}", self.configs.z_units)?;
+        writeln!(writer, "XY Units:\t{}", self.configs.xy_units)?;
+        writeln!(writer, "Projection:\t{}", self.configs.projection)?;
+        if compressed {
+            writeln!(writer, "Compressed:\t{}", COMPRESSED_MARKER)?;
+        }
+        for entry in self.metadata.iter() {
+            writeln!(writer, "Metadata Entry:\t{}", entry)?;
+        }
+        Ok(())
+    }
+
+    fn write_uncompressed(&mut self) -> Result<(), Error> {
+        self.write_header(false)?;
+        let f = File::create(&self.data_file_path())?;
+        let mut writer = BufWriter::new(f);
+        for v in self.data.iter() {
+            writer.write_all(&v.to_bits().to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn write_compressed(&mut self) -> Result<(), Error> {
+        self.write_header(true)?;
+        let f = File::create(&self.data_file_path())?;
+        let mut writer = BufWriter::new(f);
+
+        let rows = self.configs.rows;
+        let columns = self.configs.columns;
+        let mut block_index: Vec<(u64, u64)> = vec![];
+        let mut offset: u64 = 0;
+        let mut starting_row = 0;
+        while starting_row < rows {
+            let ending_row = (starting_row + ROWS_PER_COMPRESSION_BLOCK).min(rows);
+            let mut raw = Vec::with_capacity((ending_row - starting_row) * columns * 8);
+            for row in starting_row..ending_row {
+                for col in 0..columns {
+                    raw.extend_from_slice(&self.data[row * columns + col].to_bits().to_le_bytes());
+                }
+            }
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&raw)?;
+            let compressed = encoder.finish()?;
+            writer.write_all(&compressed)?;
+            block_index.push((offset, compressed.len() as u64));
+            offset += compressed.len() as u64;
+            starting_row = ending_row;
+        }
+
+        for (block_offset, block_len) in block_index.iter() {
+            writer.write_all(&block_offset.to_le_bytes())?;
+            writer.write_all(&block_len.to_le_bytes())?;
+        }
+        writer.write_all(&(block_index.len() as u64).to_le_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Index<(isize, isize)> for Raster {
+    type Output = f64;
+
+    fn index(&self, position: (isize, isize)) -> &f64 {
+        let (row, col) = position;
+        if row < 0 || col < 0 || row as usize >= self.configs.rows || col as usize >= self.configs.columns {
+            return &self.configs.nodata;
+        }
+        if !self.configs.compress {
+            return &self.data[row as usize * self.configs.columns + col as usize];
+        }
+
+        let block = row as usize / ROWS_PER_COMPRESSION_BLOCK;
+        let row_in_block = row as usize % ROWS_PER_COMPRESSION_BLOCK;
+        let columns = self.configs.columns;
+        let block_data = self.block_cache[block].get_or_init(|| self.decompress_block(block).into_boxed_slice());
+        &block_data[row_in_block * columns + col as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64;
+
+    fn temp_file(name: &str) -> String {
+        let mut p = ::std::env::temp_dir();
+        p.push(name);
+        p.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn compressed_round_trip_preserves_values_and_nodata() {
+        let file_name = temp_file("whitebox_raster_compress_test.dep");
+        let rows = ROWS_PER_COMPRESSION_BLOCK * 2 + 3;
+        let columns = 5;
+        let nodata = -32768f64;
+
+        let mut configs = RasterConfigs::default();
+        configs.rows = rows;
+        configs.columns = columns;
+        configs.compress = true;
+        let mut data = vec![0f64; rows * columns];
+        for row in 0..rows {
+            for col in 0..columns {
+                let idx = row * columns + col;
+                data[idx] = if (row + col) % 7 == 0 { nodata } else { idx as f64 };
+            }
+        }
+        // A NaN cell used to trip a `debug_assert_eq!` the moment it was indexed.
+        data[columns + 1] = f64::NAN;
+
+        let mut raster = Raster {
+            file_name: file_name.clone(),
+            file_mode: "w".to_string(),
+            configs: configs,
+            data: data.clone(),
+            metadata: vec![],
+            block_index: vec![],
+            data_file: None,
+            block_cache: vec![],
+        };
+        raster.write().unwrap();
+
+        let reader = Raster::new(&file_name, "r").unwrap();
+        assert!(reader.configs.compress);
+        for row in 0..rows {
+            for col in 0..columns {
+                let expected = data[row * columns + col];
+                let actual = reader[(row as isize, col as isize)];
+                if expected.is_nan() {
+                    assert!(actual.is_nan());
+                } else {
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
+}
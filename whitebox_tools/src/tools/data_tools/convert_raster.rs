@@ -0,0 +1,207 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 30, 2017
+Last Modified: July 30, 2017
+License: MIT
+*/
+use std::env;
+use std::path;
+use std::path::Path;
+use raster::*;
+use std::io::{Error, ErrorKind};
+use tools::WhiteboxTool;
+
+fn extension_for_format(oformat: &str) -> Result<&'static str, Error> {
+    match oformat.to_lowercase().as_ref() {
+        "dep" | "native" => Ok("dep"),
+        "tif" | "tiff" | "geotiff" => Ok("tif"),
+        "asc" | "esriascii" => Ok("asc"),
+        "bil" | "hdr" | "bandinterleaved" => Ok("hdr"),
+        _ => Err(Error::new(ErrorKind::InvalidInput,
+                            format!("Unrecognized output format '{}'. Valid options are: dep, tif, asc, bil.", oformat))),
+    }
+}
+
+pub struct ConvertRaster {
+    name: String,
+    description: String,
+    parameters: String,
+    example_usage: String,
+}
+
+impl ConvertRaster {
+    pub fn new() -> ConvertRaster { // public constructor
+        let name = "ConvertRaster".to_string();
+
+        let description = "Converts a raster between the native whitebox format, GeoTIFF, ESRI ASCII grid, and a plain band-interleaved binary.".to_string();
+
+        let mut parameters = "--input        Input raster file, in any supported format.".to_owned();
+        parameters.push_str("-o, --output   Output raster file.\n");
+        parameters.push_str("--oformat      Optional output format (dep, tif, asc, bil). Inferred from --output's extension (dep, tif, asc, hdr) if omitted.\n");
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let p = format!("{}", env::current_dir().unwrap().display());
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut short_exe = e.replace(&p, "").replace(".exe", "").replace(".", "").replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(">>.*{0} -r={1} --wd=\"*path*to*data*\" --input=in.dep -o=out.tif --oformat=tif", short_exe, name).replace("*", &sep);
+
+        ConvertRaster { name: name, description: description, parameters: parameters, example_usage: usage }
+    }
+}
+
+impl WhiteboxTool for ConvertRaster {
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        self.parameters.clone()
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn run<'a>(&self, args: Vec<String>, working_directory: &'a str, verbose: bool) -> Result<(), Error> {
+        let mut input_file = String::new();
+        let mut output_file = String::new();
+        let mut oformat = String::new();
+
+        if args.len() == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                "Tool run with no paramters. Please see help (-h) for parameter descriptions."));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("=");
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            if vec[0].to_lowercase() == "-i" || vec[0].to_lowercase() == "--input" {
+                if keyval {
+                    input_file = vec[1].to_string();
+                } else {
+                    input_file = args[i+1].to_string();
+                }
+            } else if vec[0].to_lowercase() == "-o" || vec[0].to_lowercase() == "--output" {
+                if keyval {
+                    output_file = vec[1].to_string();
+                } else {
+                    output_file = args[i+1].to_string();
+                }
+            } else if vec[0].to_lowercase() == "--oformat" {
+                if keyval {
+                    oformat = vec[1].to_string();
+                } else {
+                    oformat = args[i+1].to_string();
+                }
+            }
+        }
+
+        if verbose {
+            println!("***************{}", "*".repeat(self.get_tool_name().len()));
+            println!("* Welcome to {} *", self.get_tool_name());
+            println!("***************{}", "*".repeat(self.get_tool_name().len()));
+        }
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+
+        if !input_file.contains(&sep) {
+            input_file = format!("{}{}", working_directory, input_file);
+        }
+        if !output_file.contains(&sep) {
+            output_file = format!("{}{}", working_directory, output_file);
+        }
+        if !oformat.is_empty() {
+            let ext = extension_for_format(&oformat)?;
+            output_file = Path::new(&output_file).with_extension(ext).to_str().unwrap().to_string();
+        }
+
+        if verbose { println!("Reading data...") };
+        // Raster::new detects the source encoding from the file's own signature, so this
+        // tool never needs to know what format `input_file` was written in.
+        let input = Raster::new(&input_file, "r")?;
+
+        let rows = input.configs.rows as isize;
+        let columns = input.configs.columns as isize;
+
+        let mut output = Raster::initialize_using_file(&output_file, &input);
+        for row in 0..rows {
+            let mut row_data: Vec<f64> = vec![0f64; columns as usize];
+            for col in 0..columns {
+                row_data[col as usize] = input[(row, col)];
+            }
+            output.set_row_data(row, row_data);
+        }
+        output.add_metadata_entry(format!("Converted by whitebox_tools\' {} tool from {}", self.get_tool_name(), input_file));
+
+        if verbose { println!("Saving data...") };
+        output.write()?;
+
+        if verbose { println!("Output file written") };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> String {
+        let mut p = ::std::env::temp_dir();
+        p.push(name);
+        p.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn convert_raster_round_trips_band_interleaved() {
+        let input_file = temp_file("whitebox_convert_raster_test_input.dep");
+        let output_file = temp_file("whitebox_convert_raster_test_output.hdr");
+
+        let rows = 3;
+        let columns = 2;
+        let mut base = Raster::new(&input_file, "w").unwrap();
+        base.configs.rows = rows;
+        base.configs.columns = columns;
+        let mut input = Raster::initialize_using_file(&input_file, &base);
+        for row in 0..rows as isize {
+            let row_data: Vec<f64> = (0..columns).map(|col| (row as usize * columns + col) as f64).collect();
+            input.set_row_data(row, row_data);
+        }
+        input.write().unwrap();
+
+        let tool = ConvertRaster::new();
+        let args = vec![
+            format!("--input={}", input_file),
+            format!("--output={}", output_file),
+            "--oformat=bil".to_string(),
+        ];
+        tool.run(args, "", false).unwrap();
+
+        // The header and data file must be distinct paths, or the write in `run` above
+        // would have clobbered one with the other.
+        let data_file = Path::new(&output_file).with_extension("bil");
+        assert_ne!(output_file, data_file.to_str().unwrap());
+
+        let converted = Raster::new(&output_file, "r").unwrap();
+        assert_eq!(converted.configs.rows, rows);
+        assert_eq!(converted.configs.columns, columns);
+        for row in 0..rows as isize {
+            for col in 0..columns as isize {
+                assert_eq!(converted[(row, col)], (row as usize * columns + col as usize) as f64);
+            }
+        }
+    }
+}
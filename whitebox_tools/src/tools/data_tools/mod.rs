@@ -0,0 +1,3 @@
+mod convert_raster;
+
+pub use self::convert_raster::ConvertRaster;
@@ -0,0 +1,168 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 9, 2017
+Last Modified: July 23, 2017
+License: MIT
+*/
+extern crate time;
+
+use std::f64;
+use std::str::FromStr;
+use std::sync::Arc;
+use raster::*;
+use std::io::{Error, ErrorKind};
+use tools::parallel_row_map;
+
+/// The set of Boolean operators supported by the `boolean_overlay` engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOperator {
+    Or,
+    And,
+    Xor,
+    Nor,
+    Nand,
+    Majority,
+}
+
+impl FromStr for BooleanOperator {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<BooleanOperator, Error> {
+        match s.to_lowercase().as_ref() {
+            "or" => Ok(BooleanOperator::Or),
+            "and" => Ok(BooleanOperator::And),
+            "xor" => Ok(BooleanOperator::Xor),
+            "nor" => Ok(BooleanOperator::Nor),
+            "nand" => Ok(BooleanOperator::Nand),
+            "majority" => Ok(BooleanOperator::Majority),
+            _ => Err(Error::new(ErrorKind::InvalidInput,
+                                format!("Unrecognized Boolean operator '{}'. Valid options are: or, and, xor, nor, nand, majority.", s))),
+        }
+    }
+}
+
+impl BooleanOperator {
+    fn evaluate(&self, values: &[f64]) -> f64 {
+        let num_true = values.iter().filter(|&&v| v != 0f64).count();
+        let n = values.len();
+        match *self {
+            BooleanOperator::Or => if num_true > 0 { 1f64 } else { 0f64 },
+            BooleanOperator::And => if num_true == n { 1f64 } else { 0f64 },
+            BooleanOperator::Nor => if num_true == 0 { 1f64 } else { 0f64 },
+            BooleanOperator::Nand => if num_true == n { 0f64 } else { 1f64 },
+            BooleanOperator::Xor => if num_true % 2 == 1 { 1f64 } else { 0f64 },
+            BooleanOperator::Majority => if num_true * 2 > n { 1f64 } else { 0f64 },
+        }
+    }
+}
+
+/// Shared n-ary Boolean overlay engine over two or more input rasters; the `Or` tool runs
+/// through it with `BooleanOperator::Or`.
+pub fn boolean_overlay(inputs: Vec<Arc<Raster>>, operator: BooleanOperator, output_file: &str,
+                        tool_name: &str, verbose: bool) -> Result<(), Error> {
+    if inputs.len() < 2 {
+        return Err(Error::new(ErrorKind::InvalidInput,
+                            "The Boolean overlay engine requires at least two input rasters."));
+    }
+
+    let rows = inputs[0].configs.rows as isize;
+    let columns = inputs[0].configs.columns as isize;
+
+    // make sure all of the input rasters share rows/columns/extent
+    for input in inputs.iter().skip(1) {
+        if input.configs.rows != inputs[0].configs.rows ||
+           input.configs.columns != inputs[0].configs.columns ||
+           input.configs.north != inputs[0].configs.north ||
+           input.configs.south != inputs[0].configs.south ||
+           input.configs.east != inputs[0].configs.east ||
+           input.configs.west != inputs[0].configs.west {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                "All input files must have the same number of rows and columns and spatial extent."));
+        }
+    }
+
+    let start = time::now();
+
+    let output_base = inputs[0].clone();
+    let nodata: Vec<f64> = inputs.iter().map(|r| r.configs.nodata).collect();
+    let row_data = parallel_row_map(rows, verbose, move |row| {
+        let mut data: Vec<f64> = vec![nodata[0]; columns as usize];
+        for col in 0..columns {
+            let mut is_nodata = false;
+            let mut values: Vec<f64> = Vec::with_capacity(inputs.len());
+            for (i, input) in inputs.iter().enumerate() {
+                let z = input[(row, col)];
+                if z == nodata[i] {
+                    is_nodata = true;
+                    break;
+                }
+                values.push(z);
+            }
+            if !is_nodata {
+                data[col as usize] = operator.evaluate(&values);
+            }
+        }
+        data
+    });
+
+    let mut output = Raster::initialize_using_file(output_file, &output_base);
+    for (row, data) in row_data.into_iter().enumerate() {
+        output.set_row_data(row as isize, data);
+    }
+
+    let end = time::now();
+    let elapsed_time = end - start;
+    output.configs.data_type = DataType::F32;
+    output.configs.palette = "qual.plt".to_string();
+    output.configs.photometric_interp = PhotometricInterpretation::Categorical;
+    output.add_metadata_entry(format!("Created by whitebox_tools\' {} tool", tool_name));
+    output.add_metadata_entry(format!("Elapsed Time (excluding I/O): {}", elapsed_time).replace("PT", ""));
+
+    if verbose { println!("Saving data...") };
+    let _ = match output.write() {
+        Ok(_) => if verbose { println!("Output file written") },
+        Err(e) => return Err(e),
+    };
+
+    println!("{}", &format!("Elapsed Time (excluding I/O): {}", elapsed_time).replace("PT", ""));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operator_truth_table() {
+        let cases: Vec<(BooleanOperator, &[f64], f64)> = vec![
+            (BooleanOperator::Or, &[0f64, 0f64, 0f64], 0f64),
+            (BooleanOperator::Or, &[0f64, 1f64, 0f64], 1f64),
+            (BooleanOperator::And, &[1f64, 1f64, 1f64], 1f64),
+            (BooleanOperator::And, &[1f64, 0f64, 1f64], 0f64),
+            (BooleanOperator::Xor, &[1f64, 0f64, 0f64], 1f64),
+            (BooleanOperator::Xor, &[1f64, 1f64, 0f64], 0f64),
+            (BooleanOperator::Nor, &[0f64, 0f64, 0f64], 1f64),
+            (BooleanOperator::Nor, &[1f64, 0f64, 0f64], 0f64),
+            (BooleanOperator::Nand, &[1f64, 1f64, 1f64], 0f64),
+            (BooleanOperator::Nand, &[1f64, 0f64, 1f64], 1f64),
+            (BooleanOperator::Majority, &[1f64, 1f64, 0f64], 1f64),
+            (BooleanOperator::Majority, &[1f64, 0f64, 0f64], 0f64),
+        ];
+        for (operator, values, expected) in cases {
+            assert_eq!(operator.evaluate(values), expected, "{:?} over {:?}", operator, values);
+        }
+    }
+
+    #[test]
+    fn from_str_parses_all_operators() {
+        assert_eq!(BooleanOperator::from_str("OR").unwrap(), BooleanOperator::Or);
+        assert_eq!(BooleanOperator::from_str("and").unwrap(), BooleanOperator::And);
+        assert_eq!(BooleanOperator::from_str("xor").unwrap(), BooleanOperator::Xor);
+        assert_eq!(BooleanOperator::from_str("nor").unwrap(), BooleanOperator::Nor);
+        assert_eq!(BooleanOperator::from_str("nand").unwrap(), BooleanOperator::Nand);
+        assert_eq!(BooleanOperator::from_str("majority").unwrap(), BooleanOperator::Majority);
+        assert!(BooleanOperator::from_str("bogus").is_err());
+    }
+}
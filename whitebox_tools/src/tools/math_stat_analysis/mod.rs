@@ -0,0 +1,7 @@
+mod or;
+mod boolean_overlay;
+mod verify_raster;
+
+pub use self::or::Or;
+pub use self::boolean_overlay::{boolean_overlay, BooleanOperator};
+pub use self::verify_raster::VerifyRaster;
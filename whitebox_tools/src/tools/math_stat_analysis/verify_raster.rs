@@ -0,0 +1,319 @@
+/*
+This tool is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 16, 2017
+Last Modified: July 16, 2017
+License: MIT
+*/
+use std::env;
+use std::f64;
+use std::path;
+use raster::*;
+use std::io::{Error, ErrorKind};
+use tools::WhiteboxTool;
+
+/// FNV-1a 64-bit hash, folded over the raw bit pattern of every non-nodata cell in row-major
+/// order. Cheap to compute and sensitive enough to catch silent truncation or byte corruption
+/// of a `.tas` file between pipeline stages; it is not a cryptographic checksum.
+fn compute_checksum(raster: &Raster) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let rows = raster.configs.rows as isize;
+    let columns = raster.configs.columns as isize;
+    let nodata = raster.configs.nodata;
+    let mut hash = FNV_OFFSET;
+    for row in 0..rows {
+        for col in 0..columns {
+            let z = raster[(row, col)];
+            if z == nodata {
+                continue;
+            }
+            for byte in z.to_bits().to_le_bytes().iter() {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+    }
+    hash
+}
+
+/// Returns the valid numeric range a `DataType` can represent, used to flag values that
+/// could not have come from a correctly encoded grid of that type. `None` for data types
+/// with no meaningful bound (e.g. `F64`/`F32`, which only need a NaN check).
+fn valid_range(data_type: DataType) -> Option<(f64, f64)> {
+    match data_type {
+        DataType::U8 => Some((0f64, 255f64)),
+        DataType::I8 => Some((-128f64, 127f64)),
+        DataType::U16 => Some((0f64, 65535f64)),
+        DataType::I16 => Some((-32768f64, 32767f64)),
+        DataType::U32 => Some((0f64, 4294967295f64)),
+        DataType::I32 => Some((-2147483648f64, 2147483647f64)),
+        DataType::F32 | DataType::F64 | DataType::Unknown => None,
+    }
+}
+
+/// The outcome of verifying a single raster's internal consistency.
+struct RasterReport {
+    file_name: String,
+    checksum: Option<u64>,
+}
+
+pub struct VerifyRaster {
+    name: String,
+    description: String,
+    parameters: String,
+    example_usage: String,
+}
+
+impl VerifyRaster {
+    pub fn new() -> VerifyRaster { // public constructor
+        let name = "VerifyRaster".to_string();
+
+        let description = "Checks one or more rasters for grid integrity, extent agreement, and nodata consistency.".to_string();
+
+        let mut parameters = "--inputs       Comma separated list of one or more input raster files.".to_owned();
+        parameters.push_str("--checksum     Optional flag; compute a content checksum for each input and, when no fatal inconsistency is found, store it in its header metadata (GeoTIFF and ESRI ASCII grids are rewritten without it).\n");
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+        let p = format!("{}", env::current_dir().unwrap().display());
+        let e = format!("{}", env::current_exe().unwrap().display());
+        let mut short_exe = e.replace(&p, "").replace(".exe", "").replace(".", "").replace(&sep, "");
+        if e.contains(".exe") {
+            short_exe += ".exe";
+        }
+        let usage = format!(">>.*{0} -r={1} --wd=\"*path*to*data*\" --inputs='in1.dep,in2.dep' --checksum", short_exe, name).replace("*", &sep);
+
+        VerifyRaster { name: name, description: description, parameters: parameters, example_usage: usage }
+    }
+}
+
+impl WhiteboxTool for VerifyRaster {
+    fn get_tool_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_tool_description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn get_tool_parameters(&self) -> String {
+        self.parameters.clone()
+    }
+
+    fn get_example_usage(&self) -> String {
+        self.example_usage.clone()
+    }
+
+    fn run<'a>(&self, args: Vec<String>, working_directory: &'a str, verbose: bool) -> Result<(), Error> {
+        let mut inputs_list = String::new();
+        let mut compute_sums = false;
+
+        if args.len() == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                "Tool run with no paramters. Please see help (-h) for parameter descriptions."));
+        }
+        for i in 0..args.len() {
+            let mut arg = args[i].replace("\"", "");
+            arg = arg.replace("\'", "");
+            let cmd = arg.split("=");
+            let vec = cmd.collect::<Vec<&str>>();
+            let mut keyval = false;
+            if vec.len() > 1 {
+                keyval = true;
+            }
+            if vec[0].to_lowercase() == "-i" || vec[0].to_lowercase() == "--inputs" {
+                if keyval {
+                    inputs_list = vec[1].to_string();
+                } else {
+                    inputs_list = args[i+1].to_string();
+                }
+            } else if vec[0].to_lowercase() == "-checksum" || vec[0].to_lowercase() == "--checksum" {
+                compute_sums = true;
+            }
+        }
+
+        if verbose {
+            println!("***************{}", "*".repeat(self.get_tool_name().len()));
+            println!("* Welcome to {} *", self.get_tool_name());
+            println!("***************{}", "*".repeat(self.get_tool_name().len()));
+        }
+
+        let sep: String = path::MAIN_SEPARATOR.to_string();
+
+        let mut input_files: Vec<String> = inputs_list.split(",").map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        if input_files.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                                "At least one input file must be specified with --inputs."));
+        }
+        for f in input_files.iter_mut() {
+            if !f.contains(&sep) {
+                *f = format!("{}{}", working_directory, f);
+            }
+        }
+
+        if verbose { println!("Reading data...") };
+        let mut rasters: Vec<Raster> = Vec::with_capacity(input_files.len());
+        for f in input_files.iter() {
+            rasters.push(Raster::new(f, "r")?);
+        }
+
+        let mut fatal_errors: Vec<String> = vec![];
+        let mut reports: Vec<RasterReport> = vec![];
+
+        for (i, raster) in rasters.iter().enumerate() {
+            let expected_len = raster.configs.rows * raster.configs.columns;
+            if expected_len == 0 {
+                fatal_errors.push(format!("{}: header declares zero rows or columns.", input_files[i]));
+            }
+
+            let rows = raster.configs.rows as isize;
+            let columns = raster.configs.columns as isize;
+            let nodata = raster.configs.nodata;
+            let range = valid_range(raster.configs.data_type);
+            let mut nan_mismatches = 0usize;
+            let mut out_of_range = 0usize;
+            for row in 0..rows {
+                for col in 0..columns {
+                    let z = raster[(row, col)];
+                    if z == nodata {
+                        continue;
+                    }
+                    if z.is_nan() || z.is_infinite() {
+                        nan_mismatches += 1;
+                        continue;
+                    }
+                    if let Some((min, max)) = range {
+                        if z < min || z > max {
+                            out_of_range += 1;
+                        }
+                    }
+                }
+            }
+
+            if verbose {
+                println!("{}: {} rows x {} columns, {} NaN/Inf cells not flagged as nodata, {} cells outside the valid range for {:?}.",
+                        input_files[i], raster.configs.rows, raster.configs.columns, nan_mismatches, out_of_range, raster.configs.data_type);
+            }
+
+            if nan_mismatches > 0 {
+                fatal_errors.push(format!("{}: {} cells are NaN/infinite but not declared as the raster's nodata value.", input_files[i], nan_mismatches));
+            }
+            if out_of_range > 0 {
+                fatal_errors.push(format!("{}: {} cells fall outside the valid range for data type {:?}.", input_files[i], out_of_range, raster.configs.data_type));
+            }
+
+            reports.push(RasterReport {
+                file_name: input_files[i].clone(),
+                checksum: if compute_sums { Some(compute_checksum(raster)) } else { None },
+            });
+        }
+
+        if rasters.len() > 1 {
+            let base = &rasters[0].configs;
+            for (i, raster) in rasters.iter().enumerate().skip(1) {
+                let c = &raster.configs;
+                if c.rows != base.rows || c.columns != base.columns ||
+                   c.north != base.north || c.south != base.south ||
+                   c.east != base.east || c.west != base.west {
+                    fatal_errors.push(format!("{} does not share rows/columns/extent with {}.", input_files[i], input_files[0]));
+                }
+                if (c.resolution_x - base.resolution_x).abs() > f64::EPSILON ||
+                   (c.resolution_y - base.resolution_y).abs() > f64::EPSILON {
+                    fatal_errors.push(format!("{} has a different cell resolution than {}.", input_files[i], input_files[0]));
+                }
+            }
+        }
+
+        for report in reports.iter() {
+            if let Some(checksum) = report.checksum {
+                println!("{}: checksum = {:016x}", report.file_name, checksum);
+            }
+        }
+
+        if !fatal_errors.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, fatal_errors.join(" ")));
+        }
+
+        // Only stamp the checksum once the raster has passed every check above -- a tool
+        // whose job is to flag corrupt/inconsistent rasters should not also be the one
+        // writing to them when it has just found a problem.
+        if compute_sums {
+            for (i, raster) in rasters.iter_mut().enumerate() {
+                if let Some(checksum) = reports[i].checksum {
+                    raster.add_metadata_entry(format!("Content checksum (FNV-1a): {:016x}", checksum));
+                    // `write()` only knows how to write the flat, uncompressed `data`
+                    // vec; a raster read with `configs.compress == true` leaves that
+                    // empty in favour of the lazy block cache, so fill it in first, then
+                    // restore the original compress flag so write() re-compresses instead
+                    // of silently changing the on-disk format of the user's raster.
+                    let was_compressed = raster.configs.compress;
+                    raster.load_all();
+                    raster.configs.compress = was_compressed;
+                    raster.write()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> String {
+        let mut p = ::std::env::temp_dir();
+        p.push(name);
+        p.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn checksum_does_not_panic_on_compressed_input() {
+        let file_name = temp_file("whitebox_verify_raster_compressed_test.dep");
+        let rows = 5;
+        let columns = 4;
+        let mut base = Raster::new(&file_name, "w").unwrap();
+        base.configs.rows = rows;
+        base.configs.columns = columns;
+        let mut input = Raster::initialize_using_file(&file_name, &base);
+        input.configs.compress = true;
+        for row in 0..rows as isize {
+            let row_data: Vec<f64> = (0..columns).map(|col| (row as usize * columns + col) as f64).collect();
+            input.set_row_data(row, row_data);
+        }
+        input.write().unwrap();
+
+        let tool = VerifyRaster::new();
+        let args = vec![format!("--inputs={}", file_name), "--checksum".to_string()];
+        let result = tool.run(args, "", false);
+        assert!(result.is_ok());
+
+        // Stamping the checksum must not silently decompress the raster on disk.
+        let reread = Raster::new(&file_name, "r").unwrap();
+        assert!(reread.configs.compress);
+    }
+
+    #[test]
+    fn checksum_is_not_written_when_fatal_errors_are_found() {
+        let file_name = temp_file("whitebox_verify_raster_fatal_error_test.dep");
+        let rows = 2;
+        let columns = 2;
+        let mut base = Raster::new(&file_name, "w").unwrap();
+        base.configs.rows = rows;
+        base.configs.columns = columns;
+        let mut input = Raster::initialize_using_file(&file_name, &base);
+        // A NaN cell that isn't the raster's nodata value is a fatal error.
+        input.set_row_data(0, vec![f64::NAN, 1f64]);
+        input.set_row_data(1, vec![2f64, 3f64]);
+        input.write().unwrap();
+
+        let tool = VerifyRaster::new();
+        let args = vec![format!("--inputs={}", file_name), "--checksum".to_string()];
+        let result = tool.run(args, "", false);
+        assert!(result.is_err());
+
+        let header = ::std::fs::read_to_string(&file_name).unwrap();
+        assert!(!header.contains("Content checksum"));
+    }
+}
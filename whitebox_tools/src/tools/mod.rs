@@ -0,0 +1,80 @@
+/*
+This module is part of the WhiteboxTools geospatial analysis library.
+Authors: Dr. John Lindsay
+Created: July 2, 2017
+Last Modified: July 23, 2017
+License: MIT
+*/
+extern crate num_cpus;
+
+use std::io::Error;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+pub mod data_tools;
+pub mod math_stat_analysis;
+
+pub trait WhiteboxTool {
+    fn get_tool_name(&self) -> String;
+    fn get_tool_description(&self) -> String;
+    fn get_tool_parameters(&self) -> String;
+    fn get_example_usage(&self) -> String;
+    fn run<'a>(&self, args: Vec<String>, working_directory: &'a str, verbose: bool) -> Result<(), Error>;
+}
+
+/// Maps `row_fn` over `0..rows` across `num_cpus::get()` threads, striping rows (thread `t`
+/// takes rows `t`, `t + num_procs`, ...) to keep load balanced, and returns the results in
+/// row order.
+pub fn parallel_row_map<F>(rows: isize, verbose: bool, row_fn: F) -> Vec<Vec<f64>>
+    where F: Fn(isize) -> Vec<f64> + Send + Sync + 'static
+{
+    let row_fn = Arc::new(row_fn);
+    let num_procs = num_cpus::get();
+    let (tx, rx) = mpsc::channel();
+    for t in 0..num_procs {
+        let row_fn = row_fn.clone();
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let mut row = t as isize;
+            while row < rows {
+                let data = row_fn(row);
+                tx.send((row, data)).unwrap();
+                row += num_procs as isize;
+            }
+        });
+    }
+    drop(tx);
+
+    let mut result: Vec<Vec<f64>> = vec![vec![]; rows.max(0) as usize];
+    let mut progress: usize;
+    let mut old_progress: usize = 1;
+    for r in 0..rows {
+        let (row, data) = rx.recv().unwrap();
+        result[row as usize] = data;
+
+        if verbose {
+            progress = (100.0_f64 * r as f64 / (rows - 1).max(1) as f64) as usize;
+            if progress != old_progress {
+                println!("Progress: {}%", progress);
+                old_progress = progress;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_row_map_covers_every_row_exactly_once_in_order() {
+        let rows = 97isize; // deliberately not a multiple of a typical core count
+        let result = parallel_row_map(rows, false, |row| vec![row as f64, (row * 2) as f64]);
+        assert_eq!(result.len(), rows as usize);
+        for (row, data) in result.iter().enumerate() {
+            assert_eq!(*data, vec![row as f64, (row * 2) as f64]);
+        }
+    }
+}